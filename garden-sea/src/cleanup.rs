@@ -1,59 +1,427 @@
+use std::env;
+use std::fs;
+use std::io;
 use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+
+use crate::lock;
 use crate::log::debug;
-use eyre::Result;
+use eyre::{Result, WrapErr};
 use std::path::PathBuf;
 
-// background cleanup thread.
-pub(crate) fn start_cleanup_thread(cleanup_dirs: Vec<PathBuf>, current_dir: PathBuf) {
-    std::thread::spawn(move || {
-        for dir in cleanup_dirs {
-            if dir != current_dir {
-                debug!("Determining if {:?} is currently used...", dir);
-                let result = is_directory_used(&dir);
-                if let Ok(is_in_use) = result {
-                    if is_in_use {
-                        debug!("Skipping {:?} as it is currently in use", dir);
-                        continue;
-                    }
-                } else {
+/// What happened to a single candidate directory passed to [`start_cleanup_thread`], sent back
+/// over a channel so a caller can report a summary of what was (or, under
+/// [`RetentionPolicy::dry_run`], would have been) reclaimed.
+#[derive(Debug)]
+pub(crate) enum CleanupOutcome {
+    /// The directory was removed (or, in dry-run mode, would have been).
+    Removed(PathBuf),
+    /// Left alone because it looked like it's still in use by a running or lock-holding process.
+    SkippedInUse(PathBuf),
+    /// Left alone because it's the directory this invocation is about to run out of.
+    SkippedCurrent(PathBuf),
+    /// Left alone because it (or something inside it) matched a protect pattern.
+    SkippedProtected(PathBuf),
+    /// Left alone because the retention policy (`protected`, `keep_most_recent`, or `max_age`)
+    /// chose to keep it, independent of whether anything is actually using it.
+    SkippedRetained(PathBuf),
+    /// Removal was attempted but failed, e.g. a permissions error.
+    Failed(PathBuf, io::Error),
+}
+
+/// A one-time snapshot of whatever `is_directory_used`'s process-scan fallback needs, taken
+/// once per cleanup run rather than once per candidate directory. On unix that's the list of
+/// every running process' executable path; on Windows the rename-based check is already a cheap
+/// per-directory syscall, so there's nothing to snapshot.
+#[cfg(unix)]
+type ProcessSnapshot = Vec<PathBuf>;
+#[cfg(windows)]
+type ProcessSnapshot = ();
+
+#[cfg(unix)]
+fn snapshot_processes() -> ProcessSnapshot {
+    use sysinfo::{ProcessExt, System, SystemExt};
+
+    let mut sys = System::new();
+    sys.refresh_processes();
+
+    sys.processes()
+        .iter()
+        .map(|(_pid, process)| process.exe().to_owned())
+        .collect()
+}
+
+#[cfg(windows)]
+fn snapshot_processes() -> ProcessSnapshot {}
+
+/// Controls which of the candidate directories passed to [`start_cleanup_thread`] actually get
+/// removed: some subpaths are never touched, directories younger than `max_age` are left alone,
+/// and the `keep_most_recent` newest sessions survive even if idle.
+#[derive(Debug, Default)]
+pub(crate) struct RetentionPolicy {
+    pub(crate) protected: Vec<PathBuf>,
+    pub(crate) max_age: Option<Duration>,
+    pub(crate) keep_most_recent: usize,
+    pub(crate) protect_globs: GlobSet,
+    /// When set, the cleanup thread logs and reports what it would remove without touching the
+    /// filesystem, so users get a preview before the real deletion runs.
+    pub(crate) dry_run: bool,
+}
+
+impl RetentionPolicy {
+    /// Builds a policy from `GARDEN_SEA_CLEANUP_KEEP` / `GARDEN_SEA_CLEANUP_MAX_AGE_HOURS` /
+    /// `GARDEN_SEA_CLEANUP_PROTECT_PATHS` / `GARDEN_SEA_CLEANUP_PROTECT_GLOBS` /
+    /// `GARDEN_SEA_CLEANUP_DRY_RUN`, so users get `--keep` / `--all` / `--protect` / `--dry-run`
+    /// style knobs without garden-sea needing its own CLI parser.
+    pub(crate) fn from_env() -> Self {
+        let keep_most_recent = env::var("GARDEN_SEA_CLEANUP_KEEP")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        let max_age = env::var("GARDEN_SEA_CLEANUP_MAX_AGE_HOURS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(|hours| Duration::from_secs(hours * 3600));
+
+        let protected = env::var("GARDEN_SEA_CLEANUP_PROTECT_PATHS")
+            .ok()
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+
+        let protect_globs = env::var("GARDEN_SEA_CLEANUP_PROTECT_GLOBS")
+            .ok()
+            .map(|patterns| build_protect_globs(&patterns))
+            .unwrap_or_default();
+
+        let dry_run = env::var("GARDEN_SEA_CLEANUP_DRY_RUN")
+            .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        RetentionPolicy {
+            protected,
+            max_age,
+            keep_most_recent,
+            protect_globs,
+            dry_run,
+        }
+    }
+}
+
+/// Parses a comma-separated list of glob patterns (e.g. `"**/*.pinned,mounts/**"`) into a
+/// [`GlobSet`], skipping (and logging) any pattern that fails to compile rather than failing
+/// the whole policy over one typo.
+fn build_protect_globs(patterns: &str) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => debug!("Ignoring invalid protect pattern {:?}: {:?}", pattern, e),
+        }
+    }
+
+    builder.build().unwrap_or_default()
+}
+
+/// Loads `dir/.gardenignore`, if present, as a gitignore-syntax set of *protect* patterns.
+/// Returns `None` if the file doesn't exist.
+fn load_gardenignore(dir: &Path) -> Result<Option<Gitignore>> {
+    let path = dir.join(".gardenignore");
+
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let mut builder = GitignoreBuilder::new(dir);
+    if let Some(e) = builder.add(&path) {
+        return Err(e).wrap_err_with(|| format!("Failed to parse {:?}", path));
+    }
+
+    Ok(Some(
+        builder
+            .build()
+            .wrap_err_with(|| format!("Failed to build gitignore matcher from {:?}", path))?,
+    ))
+}
+
+/// Walks `dir` and aborts (returns `true`) if any entry matches `protect_globs`, matches an
+/// opt-in `dir/.gardenignore` pattern, or is a symlink that escapes `dir` entirely (e.g. a
+/// mounted volume). Runs before every `remove_dir_all`, so a pinned artifact or mount point
+/// inside an otherwise-stale session directory is never nuked.
+fn contains_protected_path(dir: &Path, protect_globs: &GlobSet) -> Result<bool> {
+    let gardenignore = load_gardenignore(dir)?;
+
+    // Disable WalkBuilder's own ignore-file handling: the extracted tree's own .gitignore
+    // would otherwise make the walk skip exactly the paths we need to check.
+    let walker = WalkBuilder::new(dir)
+        .hidden(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .ignore(false)
+        .parents(false)
+        .build();
+
+    for entry in walker {
+        let entry = entry.wrap_err_with(|| format!("Failed to walk {:?}", dir))?;
+        let path = entry.path();
+
+        if protect_globs.is_match(path) {
+            debug!(
+                "{:?} is protected: {:?} matches a protect pattern",
+                dir, path
+            );
+            return Ok(true);
+        }
+
+        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+        if gardenignore
+            .as_ref()
+            .is_some_and(|m| m.matched(path, is_dir).is_ignore())
+        {
+            debug!(
+                "{:?} is protected: {:?} matches a .gardenignore pattern",
+                dir, path
+            );
+            return Ok(true);
+        }
+
+        if entry.path_is_symlink() {
+            if let Ok(target) = fs::canonicalize(path) {
+                if !target.starts_with(dir) {
                     debug!(
-                        "Failed to determine if {:?} is currently used: {:?}",
-                        dir, result
+                        "{:?} is protected: {:?} is a symlink pointing outside the root",
+                        dir, path
                     );
-                    continue;
+                    return Ok(true);
                 }
+            }
+        }
+    }
+
+    Ok(false)
+}
 
-                debug!("Removing {:?}...", dir);
-                let result = std::fs::remove_dir_all(&dir);
-                if let Err(e) = result {
-                    debug!("Failed to remove {:?}: {:?}", dir, e);
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Applies `policy` to `dirs` (which must not include the directory currently in use), splitting
+/// it into directories that are actually eligible for removal (oldest first) and directories the
+/// policy retains: a protected path, one of the most-recently-modified `keep_most_recent`, or
+/// younger than `max_age`. The second list is what lets a caller report every directory the
+/// policy kept, not just the ones it let through to the usage check.
+fn select_removal_candidates(
+    mut dirs: Vec<PathBuf>,
+    policy: &RetentionPolicy,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut retained = Vec::new();
+
+    dirs.retain(|dir| {
+        let eligible = !policy.protected.iter().any(|p| dir.starts_with(p));
+        if !eligible {
+            retained.push(dir.clone());
+        }
+        eligible
+    });
+
+    // oldest first, so the most recently modified dirs end up at the tail
+    dirs.sort_by_key(|dir| mtime(dir).unwrap_or(UNIX_EPOCH));
+
+    if policy.keep_most_recent > 0 {
+        let keep = policy.keep_most_recent.min(dirs.len());
+        let most_recent = dirs.split_off(dirs.len() - keep);
+        retained.extend(most_recent);
+    }
+
+    if let Some(max_age) = policy.max_age {
+        let now = SystemTime::now();
+        dirs.retain(|dir| {
+            let eligible = mtime(dir)
+                .and_then(|m| now.duration_since(m).ok())
+                .map(|age| age >= max_age)
+                .unwrap_or(true);
+            if !eligible {
+                retained.push(dir.clone());
+            }
+            eligible
+        });
+    }
+
+    (dirs, retained)
+}
+
+// background cleanup thread.
+///
+/// Returns a [`Receiver`] of [`CleanupOutcome`]s, one per candidate directory (including the
+/// current directory, reported as [`CleanupOutcome::SkippedCurrent`]), so a caller can print a
+/// summary of what was reclaimed. Cleanup still runs in the background regardless of whether
+/// anyone reads from the receiver; send errors (no receiver left) are ignored.
+pub(crate) fn start_cleanup_thread(
+    cleanup_dirs: Vec<PathBuf>,
+    current_dir: PathBuf,
+    policy: RetentionPolicy,
+) -> Receiver<CleanupOutcome> {
+    let (tx, rx) = mpsc::sync_channel(cleanup_dirs.len().max(1));
+
+    std::thread::spawn(move || {
+        let cleanup_dirs: Vec<PathBuf> = cleanup_dirs
+            .into_iter()
+            .filter(|dir| {
+                if *dir == current_dir {
+                    let _ = tx.send(CleanupOutcome::SkippedCurrent(dir.clone()));
+                    false
                 } else {
-                    debug!("Removed {:?}", dir);
+                    true
                 }
-            }
+            })
+            .collect();
+        let (candidates, retained) = select_removal_candidates(cleanup_dirs, &policy);
+
+        for dir in retained {
+            let _ = tx.send(CleanupOutcome::SkippedRetained(dir));
         }
+
+        // Snapshot the process table once up front instead of re-scanning it for every
+        // candidate directory, then fan the per-directory usage checks and removals out
+        // across a rayon thread pool: what used to be a serial pass with a fresh process-table
+        // refresh per directory becomes a single refresh and one parallel sweep, in which each
+        // directory only compares against the snapshot. This is safe to run
+        // concurrently across candidates because each one takes its own per-directory
+        // exclusive lock in `cleanup_candidate`, held for that candidate's whole removal.
+        let snapshot = snapshot_processes();
+
+        candidates.par_iter().for_each(|dir| {
+            let tx = tx.clone();
+            cleanup_candidate(dir, &snapshot, &policy, &tx);
+        });
     });
+
+    rx
 }
 
-// platform-specific code
+fn cleanup_candidate(
+    dir: &Path,
+    snapshot: &ProcessSnapshot,
+    policy: &RetentionPolicy,
+    tx: &SyncSender<CleanupOutcome>,
+) {
+    debug!("Determining if {:?} is currently used...", dir);
+    match is_directory_used(dir, snapshot) {
+        Ok(true) => {
+            debug!("Skipping {:?} as it is currently in use", dir);
+            let _ = tx.send(CleanupOutcome::SkippedInUse(dir.to_owned()));
+            return;
+        }
+        Ok(false) => {}
+        Err(e) => {
+            debug!(
+                "Failed to determine if {:?} is currently used: {:?}",
+                dir, e
+            );
+            let _ = tx.send(CleanupOutcome::Failed(dir.to_owned(), io_error(e)));
+            return;
+        }
+    }
 
-#[cfg(unix)]
-fn is_directory_used(path: &Path) -> Result<bool> {
-    use sysinfo::{ProcessExt, System, SystemExt};
+    // Even if no running process looks like it's using `dir`, another garden-sea invocation
+    // may have just claimed it (or be about to). Require an exclusive lock before deleting, so
+    // we never remove a directory whose files are still mmaped/open by a process holding the
+    // shared lock taken in `extract`.
+    // Holding the exclusive lock only for the check and releasing it before `remove_dir_all`
+    // would let another garden-sea invocation take the shared lock in the gap and exec out of
+    // `dir` while it's being unlinked. `_lock` must stay alive for the rest of this function so
+    // it's held across the whole removal, not just the check.
+    let _lock = match lock::try_exclusive(dir) {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            debug!("Skipping {:?}: locked by another process", dir);
+            let _ = tx.send(CleanupOutcome::SkippedInUse(dir.to_owned()));
+            return;
+        }
+        Err(e) => {
+            debug!("Failed to check lock for {:?}: {:?}", dir, e);
+            let _ = tx.send(CleanupOutcome::Failed(dir.to_owned(), e));
+            return;
+        }
+    };
 
-    let mut sys = System::new();
-    sys.refresh_processes();
+    // `_lock` is still held here, so nothing can start using `dir` between this check and the
+    // `remove_dir_all` below — a protected path can't be raced in after we've decided it's clear.
+    match contains_protected_path(dir, &policy.protect_globs) {
+        Ok(true) => {
+            debug!("Skipping {:?}: contains a protected path", dir);
+            let _ = tx.send(CleanupOutcome::SkippedProtected(dir.to_owned()));
+            return;
+        }
+        Ok(false) => {}
+        Err(e) => {
+            debug!(
+                "Failed to check {:?} for protected paths, leaving it alone: {:?}",
+                dir, e
+            );
+            let _ = tx.send(CleanupOutcome::Failed(dir.to_owned(), io_error(e)));
+            return;
+        }
+    }
 
-    let paths: Vec<PathBuf> = sys
-        .processes()
-        .iter()
-        .map(|(_pid, process)| {
-            return process.exe().to_owned();
-        })
-        .collect();
+    if policy.dry_run {
+        debug!("Would remove {:?} (dry run)", dir);
+        let _ = tx.send(CleanupOutcome::Removed(dir.to_owned()));
+        return;
+    }
 
-    for exe in paths {
+    debug!("Removing {:?}...", dir);
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => {
+            debug!("Removed {:?}", dir);
+            let _ = tx.send(CleanupOutcome::Removed(dir.to_owned()));
+        }
+        Err(e) => {
+            debug!("Failed to remove {:?}: {:?}", dir, e);
+            let _ = tx.send(CleanupOutcome::Failed(dir.to_owned(), e));
+        }
+    }
+}
+
+/// `is_directory_used` and `contains_protected_path` return `eyre::Report`, not `io::Error`, so
+/// wrap their context into an `io::Error` of kind `Other` to fit in [`CleanupOutcome::Failed`].
+fn io_error(e: eyre::Report) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+// platform-specific code
+
+fn is_directory_used(path: &Path, snapshot: &ProcessSnapshot) -> Result<bool> {
+    // A `.garden-lock.<pid>` file, if present and fresh, tells us directly whether the directory
+    // is in use, without having to scan every running process (which misses processes that
+    // merely `chdir`'d into it) or rely on the Windows rename trick. Only fall back to the old
+    // heuristic when no lock file exists at all, e.g. a directory left over from an older
+    // garden-sea version.
+    if let Some(in_use) = lock::directory_has_fresh_pid_lock(path)? {
+        debug!(
+            "is_directory_used: {:?} lock file is {}",
+            path,
+            if in_use { "fresh" } else { "stale" }
+        );
+        return Ok(in_use);
+    }
+
+    scan_processes_for_directory(path, snapshot)
+}
+
+#[cfg(unix)]
+fn scan_processes_for_directory(path: &Path, snapshot: &ProcessSnapshot) -> Result<bool> {
+    for exe in snapshot {
         if exe.starts_with(path) {
             debug!(
                 "is_directory_used: {:?} is in use by a running garden process.",
@@ -69,7 +437,7 @@ fn is_directory_used(path: &Path) -> Result<bool> {
 }
 
 #[cfg(windows)]
-fn is_directory_used(path: &Path) -> Result<bool> {
+fn scan_processes_for_directory(path: &Path, _snapshot: &ProcessSnapshot) -> Result<bool> {
     match std::fs::rename(path, path) {
         Ok(()) => {
             // Of course there is the possibility of races. Only way to exclude that possibility is using locks, which comes with it's own complexities.
@@ -82,3 +450,218 @@ fn is_directory_used(path: &Path) -> Result<bool> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_protect_globs, cleanup_candidate, contains_protected_path, select_removal_candidates,
+        start_cleanup_thread, CleanupOutcome, RetentionPolicy,
+    };
+    use globset::GlobSet;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// A fresh, empty directory under the system temp dir, removed when dropped. `mtime()` reads
+    /// real filesystem metadata, so `select_removal_candidates` tests need real directories
+    /// rather than bare `PathBuf`s.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "garden-sea-cleanup-test-{}-{}-{}",
+                std::process::id(),
+                name,
+                rand_suffix()
+            ));
+            std::fs::create_dir_all(&path).expect("Failed to create temp dir");
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn rand_suffix() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Creates `count` temp dirs, oldest first, sleeping between each so their mtimes are
+    /// distinguishable on filesystems with coarse mtime resolution.
+    fn aged_dirs(count: usize) -> Vec<TempDir> {
+        (0..count)
+            .map(|i| {
+                let dir = TempDir::new(&i.to_string());
+                std::thread::sleep(Duration::from_millis(10));
+                dir
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_protected_prefixes_out_of_candidates() {
+        let dirs = aged_dirs(2);
+        let policy = RetentionPolicy {
+            protected: vec![dirs[0].0.clone()],
+            ..Default::default()
+        };
+
+        let (candidates, retained) =
+            select_removal_candidates(dirs.iter().map(|d| d.0.clone()).collect(), &policy);
+
+        assert_eq!(candidates, vec![dirs[1].0.clone()]);
+        assert_eq!(retained, vec![dirs[0].0.clone()]);
+    }
+
+    #[test]
+    fn keeps_most_recent_out_of_candidates() {
+        let dirs = aged_dirs(3);
+        let policy = RetentionPolicy {
+            keep_most_recent: 1,
+            ..Default::default()
+        };
+
+        let (candidates, retained) =
+            select_removal_candidates(dirs.iter().map(|d| d.0.clone()).collect(), &policy);
+
+        // oldest two are eligible, the newest one is retained
+        assert_eq!(candidates, vec![dirs[0].0.clone(), dirs[1].0.clone()]);
+        assert_eq!(retained, vec![dirs[2].0.clone()]);
+    }
+
+    #[test]
+    fn keeps_dirs_younger_than_max_age() {
+        let dirs = aged_dirs(1);
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+
+        let (candidates, retained) =
+            select_removal_candidates(dirs.iter().map(|d| d.0.clone()).collect(), &policy);
+
+        assert!(candidates.is_empty());
+        assert_eq!(retained, vec![dirs[0].0.clone()]);
+    }
+
+    #[test]
+    fn removes_dirs_older_than_max_age() {
+        let dirs = aged_dirs(1);
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(0)),
+            ..Default::default()
+        };
+
+        let (candidates, retained) =
+            select_removal_candidates(dirs.iter().map(|d| d.0.clone()).collect(), &policy);
+
+        assert_eq!(candidates, vec![dirs[0].0.clone()]);
+        assert!(retained.is_empty());
+    }
+
+    #[test]
+    fn build_protect_globs_matches_given_patterns() {
+        let globs = build_protect_globs("**/*.pinned, mounts/**");
+
+        assert!(globs.is_match(PathBuf::from("some/dir/artifact.pinned")));
+        assert!(globs.is_match(PathBuf::from("mounts/shared")));
+        assert!(!globs.is_match(PathBuf::from("some/dir/artifact.tmp")));
+    }
+
+    #[test]
+    fn build_protect_globs_ignores_invalid_pattern_but_keeps_the_rest() {
+        let globs = build_protect_globs("[invalid,mounts/**");
+
+        assert!(globs.is_match(PathBuf::from("mounts/shared")));
+    }
+
+    #[test]
+    fn build_protect_globs_empty_string_matches_nothing() {
+        let globs = build_protect_globs("");
+
+        assert!(globs.is_empty());
+    }
+
+    #[test]
+    fn gardenignore_pattern_protects_matching_path() {
+        let dir = TempDir::new("gardenignore");
+        std::fs::write(dir.0.join(".gardenignore"), "pinned/\n").unwrap();
+        std::fs::create_dir(dir.0.join("pinned")).unwrap();
+        std::fs::write(dir.0.join("pinned").join("artifact"), b"data").unwrap();
+
+        assert!(contains_protected_path(&dir.0, &GlobSet::default()).unwrap());
+    }
+
+    #[test]
+    fn no_gardenignore_and_no_protect_globs_is_unprotected() {
+        let dir = TempDir::new("no-protection");
+        std::fs::write(dir.0.join("plain-file"), b"data").unwrap();
+
+        assert!(!contains_protected_path(&dir.0, &GlobSet::default()).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_escaping_root_is_protected_even_without_any_protect_patterns() {
+        let dir = TempDir::new("symlink-escape");
+        let outside = TempDir::new("symlink-escape-target");
+
+        std::os::unix::fs::symlink(&outside.0, dir.0.join("mount")).unwrap();
+
+        assert!(contains_protected_path(&dir.0, &GlobSet::default()).unwrap());
+    }
+
+    #[test]
+    fn dry_run_reports_removed_but_leaves_the_directory_in_place() {
+        let dir = TempDir::new("dry-run");
+        let policy = RetentionPolicy {
+            dry_run: true,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        cleanup_candidate(&dir.0, &Vec::new(), &policy, &tx);
+
+        assert!(matches!(rx.recv().unwrap(), CleanupOutcome::Removed(p) if p == dir.0));
+        assert!(dir.0.exists());
+    }
+
+    #[test]
+    fn non_dry_run_actually_removes_the_directory() {
+        let dir = TempDir::new("real-run");
+        let path = dir.0.clone();
+        let policy = RetentionPolicy::default();
+        let (tx, rx) = mpsc::sync_channel(1);
+
+        cleanup_candidate(&path, &Vec::new(), &policy, &tx);
+
+        assert!(matches!(rx.recv().unwrap(), CleanupOutcome::Removed(p) if p == path));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn start_cleanup_thread_reports_current_dir_as_skipped_and_others_as_removed() {
+        let dirs = aged_dirs(2);
+        let current_dir = dirs[1].0.clone();
+        let removable_dir = dirs[0].0.clone();
+        let cleanup_dirs = dirs.iter().map(|d| d.0.clone()).collect();
+
+        let rx = start_cleanup_thread(cleanup_dirs, current_dir.clone(), RetentionPolicy::default());
+        let outcomes: Vec<CleanupOutcome> = rx.into_iter().collect();
+
+        assert!(outcomes
+            .iter()
+            .any(|o| matches!(o, CleanupOutcome::SkippedCurrent(p) if *p == current_dir)));
+        assert!(outcomes
+            .iter()
+            .any(|o| matches!(o, CleanupOutcome::Removed(p) if *p == removable_dir)));
+        assert!(!removable_dir.exists());
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, BufReader, Read, Write};
+
+use sha2::{Digest, Sha256};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A SHA-256 digest, printable as the lowercase hex string used by `sha256sum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sha256Digest([u8; 32]);
+
+impl Sha256Digest {
+    pub(crate) fn to_hex(self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Forwards every write into a `Sha256` hasher, so the digest can be computed by streaming
+/// through a `BufReader` instead of loading the input into an intermediate buffer.
+struct HashSink(Sha256);
+
+impl Write for HashSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes the full contents of `reader` in fixed-size chunks and returns the
+/// resulting SHA-256 digest.
+pub(crate) fn sha256_digest<R: Read>(reader: R) -> io::Result<Sha256Digest> {
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, reader);
+    let mut sink = HashSink(Sha256::new());
+
+    io::copy(&mut reader, &mut sink)?;
+
+    Ok(Sha256Digest(sink.0.finalize().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sha256_digest;
+
+    #[test]
+    fn test_known_digest() {
+        let digest = sha256_digest(b"abc".as_slice()).expect("Failed to hash");
+
+        assert_eq!(
+            digest.to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let digest = sha256_digest(b"".as_slice()).expect("Failed to hash");
+
+        assert_eq!(
+            digest.to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_input_larger_than_chunk_size() {
+        // Exercise the BufReader chunking by hashing input well past CHUNK_SIZE, and check it
+        // matches hashing the same bytes directly with the `sha2` crate.
+        let data = vec![0x42u8; super::CHUNK_SIZE * 3 + 17];
+
+        let digest = sha256_digest(data.as_slice()).expect("Failed to hash");
+
+        use sha2::{Digest, Sha256};
+        let expected = Sha256::digest(&data);
+
+        assert_eq!(digest.to_hex(), format!("{:x}", expected));
+    }
+}
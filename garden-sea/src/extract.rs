@@ -1,28 +1,56 @@
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use std::env;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
 use std::time::SystemTime;
 use std::{fs, io::Write};
 
 use eyre::{Result, WrapErr};
 
 use crate::artifacts::{GardenArtifact, NATIVE_MODULES, NODE_BINARY, SOURCE, STATIC};
-use crate::cleanup::start_cleanup_thread;
+use crate::cleanup::{start_cleanup_thread, CleanupOutcome, RetentionPolicy};
+use crate::digest::sha256_digest;
+use crate::lock;
 use crate::log::debug;
 
-pub(crate) fn extract_archives_if_needed(root_path: &Path) -> Result<PathBuf> {
+#[cfg(unix)]
+const NODE_EXECUTABLE: &str = "bin/node";
+#[cfg(windows)]
+const NODE_EXECUTABLE: &str = "bin/node.exe";
+
+pub(crate) fn extract_archives_if_needed(
+    root_path: &Path,
+) -> Result<(PathBuf, lock::PidLockGuard, Receiver<CleanupOutcome>)> {
     fs::create_dir_all(root_path)?;
 
     let (latest_dir, older_dirs) = find_existing_extract_dirs(root_path)?;
 
     if let Some(p) = latest_dir {
+        // Claim the (revocable) pid lock before checking whether p needs re-extraction, not
+        // after: extracts_needed checks the node binary on disk, so there'd otherwise be a window
+        // where we've picked p but a concurrent process's cleanup thread doesn't yet see it as
+        // in use and removes it out from under us. is_directory_used checks the pid lock before
+        // ever taking the exclusive lock cleanup needs to delete, so this alone closes the race.
+        let pid_lock = lock::claim_pid_lock(&p)
+            .wrap_err_with(|| format!("Failed to claim pid lock for {:?}", p))?;
+
         if !extracts_needed(&p)? {
-            // cleanup happens in the background to avoid users waiting for it
-            start_cleanup_thread(older_dirs, p.clone());
+            // Only now leak the shared lock for the life of this process — p is actually the
+            // directory we're using. Leaking it on every inspected candidate, including ones we
+            // decide not to reuse, would make a stale p look permanently in-use to cleanup.
+            lock::hold_shared(&p).wrap_err_with(|| format!("Failed to lock {:?}", p))?;
 
-            return Ok(p);
+            // cleanup happens in the background to avoid users waiting for it; the returned
+            // receiver lets the caller print a summary of what it reclaimed once it's done
+            let cleanup_rx = start_cleanup_thread(older_dirs, p.clone(), RetentionPolicy::from_env());
+
+            return Ok((p, pid_lock, cleanup_rx));
         }
+
+        // p needs re-extraction; drop our pid lock claim on it so a later cleanup run can
+        // reclaim it instead of seeing it as permanently in use.
     }
 
     // generate a random directory name, ending with "r"
@@ -48,17 +76,38 @@ pub(crate) fn extract_archives_if_needed(root_path: &Path) -> Result<PathBuf> {
     extract_archive(&extract_path, STATIC)?;
     extract_archive(&extract_path, SOURCE)?;
 
-    // cleanup happens in the background to avoid users waiting for it
-    start_cleanup_thread(older_dirs, extract_path.clone());
+    lock::hold_shared(&extract_path)
+        .wrap_err_with(|| format!("Failed to lock {:?}", extract_path))?;
+    let pid_lock = lock::claim_pid_lock(&extract_path)
+        .wrap_err_with(|| format!("Failed to claim pid lock for {:?}", extract_path))?;
 
-    Ok(extract_path)
+    // cleanup happens in the background to avoid users waiting for it; the returned receiver
+    // lets the caller print a summary of what it reclaimed once it's done
+    let cleanup_rx =
+        start_cleanup_thread(older_dirs, extract_path.clone(), RetentionPolicy::from_env());
+
+    Ok((extract_path, pid_lock, cleanup_rx))
 }
 
 fn extracts_needed(path: &Path) -> Result<bool> {
-    Ok(is_extract_needed(path, NODE_BINARY)?
+    if is_extract_needed(path, NODE_BINARY)?
         || is_extract_needed(path, NATIVE_MODULES)?
         || is_extract_needed(path, STATIC)?
-        || is_extract_needed(path, SOURCE)?)
+        || is_extract_needed(path, SOURCE)?
+    {
+        return Ok(true);
+    }
+
+    // The checksum files only tell us the embedded archives matched what we extracted at the
+    // time. Check the node binary we're about to execute against the size (and, when
+    // GARDEN_SEA_VERIFY_NODE_BINARY is set, the digest) recorded at extraction, so a cache
+    // truncated or corrupted after the fact gets rebuilt instead of silently executed.
+    if !node_binary_is_intact(path)? {
+        debug!("node binary failed integrity check, forcing re-extraction");
+        return Ok(true);
+    }
+
+    Ok(false)
 }
 
 fn find_existing_extract_dirs(root_path: &Path) -> Result<(Option<PathBuf>, Vec<PathBuf>)> {
@@ -131,6 +180,9 @@ fn extract_archive(path: &Path, artifact: GardenArtifact) -> Result<()> {
         artifact.archive.len()
     );
 
+    verify_archive_digest(artifact)
+        .wrap_err_with(|| format!("Refusing to extract corrupt archive {}", artifact.name))?;
+
     // if not match, extract the NODE_BINARY_ARCHIVE
     unpack(path, artifact.archive).wrap_err_with(|| {
         format!(
@@ -141,6 +193,11 @@ fn extract_archive(path: &Path, artifact: GardenArtifact) -> Result<()> {
 
     debug!("{}: Successfully extracted to {:?}", artifact.name, path);
 
+    if artifact.name == NODE_BINARY.name {
+        write_node_binary_digest(path)
+            .wrap_err("Failed to record node binary digest after extraction")?;
+    }
+
     let checksum_file = path.join(format!("{}.sha256sum", artifact.name));
 
     // write the checksum file
@@ -152,6 +209,95 @@ fn extract_archive(path: &Path, artifact: GardenArtifact) -> Result<()> {
     Ok(())
 }
 
+// checksum verification
+
+/// Hashes the embedded archive bytes and asserts they match `artifact.sha256`, so a
+/// truncated or corrupted embedded archive is caught before we ever unpack it.
+fn verify_archive_digest(artifact: GardenArtifact) -> Result<()> {
+    let actual = sha256_digest(artifact.archive)
+        .wrap_err_with(|| format!("Failed to hash archive {}", artifact.name))?
+        .to_hex();
+
+    let expected = String::from_utf8_lossy(artifact.sha256);
+    // Accept the standard `sha256sum`-style shape ("<hex>  <filename>\n"), not just a bare hex
+    // string, since that's the common convention for a generated `.sha256` file.
+    let expected = expected.split_whitespace().next().unwrap_or("");
+
+    if actual != expected {
+        return Err(eyre::eyre!(
+            "Checksum mismatch for archive {}: expected {}, got {}",
+            artifact.name,
+            expected,
+            actual
+        ));
+    }
+
+    Ok(())
+}
+
+fn node_binary_digest_file(path: &Path) -> PathBuf {
+    path.join("bin").join("node.extracted.sha256sum")
+}
+
+/// Records the digest and size of the just-extracted node executable, so future runs can detect
+/// if the file on disk was corrupted after extraction.
+fn write_node_binary_digest(path: &Path) -> Result<()> {
+    let node_binary = path.join(NODE_EXECUTABLE);
+    let digest_file = node_binary_digest_file(path);
+
+    let digest = sha256_digest(fs::File::open(&node_binary)?)
+        .wrap_err_with(|| format!("Failed to hash {:?}", node_binary))?;
+    let size = fs::metadata(&node_binary)
+        .wrap_err_with(|| format!("Failed to stat {:?}", node_binary))?
+        .len();
+
+    fs::write(&digest_file, format!("{} {}", digest.to_hex(), size))
+        .wrap_err_with(|| format!("Failed to write {:?}", digest_file))
+}
+
+/// Checks the extracted node executable against what was recorded at extraction time. The file
+/// size is compared first, which is a cheap `stat()` that catches most corruption (truncation, a
+/// killed extraction) without reading the whole (~100MB) file. A full re-hash only runs when
+/// `GARDEN_SEA_VERIFY_NODE_BINARY` opts into it, since hashing it on every invocation would add
+/// real latency to the fast path. Returns `false` if the binary or its recorded digest is
+/// missing, or if either check fails.
+fn node_binary_is_intact(path: &Path) -> Result<bool> {
+    let node_binary = path.join(NODE_EXECUTABLE);
+    let digest_file = node_binary_digest_file(path);
+
+    if !node_binary.exists() || !digest_file.exists() {
+        return Ok(false);
+    }
+
+    let recorded = fs::read_to_string(&digest_file)
+        .wrap_err_with(|| format!("Failed to read {:?}", digest_file))?;
+    let mut fields = recorded.split_whitespace();
+    let expected_digest = fields.next().unwrap_or("");
+    let expected_size: Option<u64> = fields.next().and_then(|s| s.parse().ok());
+
+    let actual_size = fs::metadata(&node_binary)
+        .wrap_err_with(|| format!("Failed to stat {:?}", node_binary))?
+        .len();
+
+    if expected_size != Some(actual_size) {
+        return Ok(false);
+    }
+
+    let verify_hash = env::var("GARDEN_SEA_VERIFY_NODE_BINARY")
+        .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    if !verify_hash {
+        return Ok(true);
+    }
+
+    let actual_digest = sha256_digest(fs::File::open(&node_binary)?)
+        .wrap_err_with(|| format!("Failed to hash {:?}", node_binary))?
+        .to_hex();
+
+    Ok(actual_digest == expected_digest)
+}
+
 fn unpack(path: &Path, archive: &[u8]) -> Result<(), std::io::Error> {
     // extract the NODE_BINARY_ARCHIVE
     let decoder = flate2::read::GzDecoder::new(archive);
@@ -162,6 +308,46 @@ fn unpack(path: &Path, archive: &[u8]) -> Result<(), std::io::Error> {
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::verify_archive_digest;
+    use crate::artifacts::GardenArtifact;
+
+    #[test]
+    fn verify_archive_digest_accepts_bare_hex() {
+        let artifact = GardenArtifact {
+            name: "test",
+            archive: b"abc",
+            sha256: b"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        };
+
+        assert!(verify_archive_digest(artifact).is_ok());
+    }
+
+    #[test]
+    fn verify_archive_digest_accepts_sha256sum_style_output() {
+        // The shape produced by `sha256sum file > file.sha256`: hex digest, two spaces, filename.
+        let artifact = GardenArtifact {
+            name: "test",
+            archive: b"abc",
+            sha256: b"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  archive.tar.gz\n",
+        };
+
+        assert!(verify_archive_digest(artifact).is_ok());
+    }
+
+    #[test]
+    fn verify_archive_digest_rejects_mismatch() {
+        let artifact = GardenArtifact {
+            name: "test",
+            archive: b"abc",
+            sha256: b"0000000000000000000000000000000000000000000000000000000000000000",
+        };
+
+        assert!(verify_archive_digest(artifact).is_err());
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::{fs, path::PathBuf};
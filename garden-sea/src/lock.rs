@@ -0,0 +1,260 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use fs4::FileExt;
+
+const LOCK_FILE_NAME: &str = ".garden-sea.lock";
+
+fn lock_file(dir: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .open(dir.join(LOCK_FILE_NAME))
+}
+
+/// Takes a shared (advisory, flock-based) lock on `dir` for the lifetime of this process, so
+/// a concurrent garden-sea's cleanup thread can tell we're using it and leaves it alone.
+///
+/// The OS releases the lock when the process exits, so we intentionally leak the file handle
+/// rather than threading a guard value through callers.
+pub(crate) fn hold_shared(dir: &Path) -> io::Result<()> {
+    let file = lock_file(dir)?;
+    file.lock_shared()?;
+    Box::leak(Box::new(file));
+
+    Ok(())
+}
+
+/// Attempts to take an exclusive lock on `dir`, returning the locked [`File`] only if no other
+/// process currently holds a (shared or exclusive) lock on it. Callers must keep the returned
+/// `File` alive for the full duration of whatever they delete `dir` for — the OS releases the
+/// lock as soon as it's dropped.
+pub(crate) fn try_exclusive(dir: &Path) -> io::Result<Option<File>> {
+    let file = lock_file(dir)?;
+
+    match file.try_lock_exclusive() {
+        Ok(()) => Ok(Some(file)),
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// PID lock files
+//
+// A garden-sea process records its pid and hostname in a small lock file as soon as it starts
+// using a directory, so other invocations (and their cleanup threads) can check that directly
+// instead of scanning the process table for a matching executable path (which misses processes
+// that merely chdir'd into the directory).
+//
+// Each holder gets its own file (`.garden-lock.<pid>`) rather than sharing one, so two processes
+// claiming the same directory at once can't stomp or delete each other's record.
+
+const PID_LOCK_FILE_PREFIX: &str = ".garden-lock.";
+
+/// Removes our pid lock file when dropped (i.e. on normal process exit), so a clean shutdown
+/// leaves no stale lock behind. Other concurrent holders' lock files are untouched.
+pub(crate) struct PidLockGuard(PathBuf);
+
+impl Drop for PidLockGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn local_hostname() -> io::Result<String> {
+    Ok(hostname::get()?.to_string_lossy().into_owned())
+}
+
+fn pid_lock_path(dir: &Path, pid: u32) -> PathBuf {
+    dir.join(format!("{}{}", PID_LOCK_FILE_PREFIX, pid))
+}
+
+/// Every `.garden-lock.<pid>` file currently present directly under `dir`: one per concurrent
+/// holder, rather than a single winner-takes-all file.
+fn existing_pid_locks(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut locks = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+        let is_pid_lock = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(PID_LOCK_FILE_PREFIX));
+
+        if is_pid_lock {
+            locks.push(path);
+        }
+    }
+
+    Ok(locks)
+}
+
+/// Creates (or overwrites a stale leftover of) our own `<dir>/.garden-lock.<pid>`, recording our
+/// pid and hostname so other garden-sea processes can tell this directory is in use.
+pub(crate) fn claim_pid_lock(dir: &Path) -> io::Result<PidLockGuard> {
+    let path = pid_lock_path(dir, std::process::id());
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)?;
+
+    writeln!(file, "{}\n{}", std::process::id(), local_hostname()?)?;
+
+    Ok(PidLockGuard(path))
+}
+
+/// A directory is in use according to its lock file if the file exists, was written by this
+/// host, and the recorded pid is still alive. A lock written by a different hostname (e.g. a
+/// shared network temp dir) can't be checked for liveness, so it's conservatively treated as
+/// fresh.
+pub(crate) fn pid_lock_is_fresh(lock_file: &Path) -> io::Result<bool> {
+    let contents = fs::read_to_string(lock_file)?;
+    let mut lines = contents.lines();
+
+    let pid: Option<u32> = lines.next().and_then(|s| s.parse().ok());
+    let hostname = lines.next();
+
+    let (Some(pid), Some(hostname)) = (pid, hostname) else {
+        // Couldn't parse it, e.g. a lock file from an incompatible future version. Assume fresh.
+        return Ok(true);
+    };
+
+    if hostname != local_hostname()? {
+        return Ok(true);
+    }
+
+    Ok(process_is_alive(pid))
+}
+
+/// Looks for `<dir>/.garden-lock.<pid>` files and reports whether any of them indicates the
+/// directory is still in use. Returns `None` if no lock file is present at all, so callers can
+/// fall back to another heuristic. Any lock found to belong to a dead process is removed as a
+/// side effect, so a crashed holder's file doesn't linger forever.
+pub(crate) fn directory_has_fresh_pid_lock(dir: &Path) -> io::Result<Option<bool>> {
+    let locks = existing_pid_locks(dir)?;
+
+    if locks.is_empty() {
+        return Ok(None);
+    }
+
+    let mut any_fresh = false;
+
+    for lock_file in &locks {
+        match pid_lock_is_fresh(lock_file) {
+            Ok(true) => any_fresh = true,
+            Ok(false) => {
+                let _ = fs::remove_file(lock_file);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(Some(any_fresh))
+}
+
+#[cfg(unix)]
+fn process_is_alive(pid: u32) -> bool {
+    // EPERM means the process exists but belongs to someone else; only ESRCH means it's gone.
+    !matches!(
+        nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid.try_into().unwrap()), None),
+        Err(nix::errno::Errno::ESRCH)
+    )
+}
+
+#[cfg(windows)]
+fn process_is_alive(pid: u32) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(handle) => {
+                let _ = CloseHandle(handle);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{claim_pid_lock, directory_has_fresh_pid_lock, local_hostname, pid_lock_path};
+    use std::path::PathBuf;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            use std::sync::atomic::{AtomicU64, Ordering};
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "garden-sea-lock-test-{}-{}-{}",
+                std::process::id(),
+                name,
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir_all(&path).expect("Failed to create temp dir");
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn claim_pid_lock_is_reported_fresh_until_dropped() {
+        let dir = TempDir::new("round-trip");
+
+        let guard = claim_pid_lock(&dir.0).expect("Failed to claim pid lock");
+        assert_eq!(directory_has_fresh_pid_lock(&dir.0).unwrap(), Some(true));
+
+        drop(guard);
+        assert_eq!(directory_has_fresh_pid_lock(&dir.0).unwrap(), None);
+    }
+
+    #[test]
+    fn no_lock_file_reports_none() {
+        let dir = TempDir::new("no-lock");
+
+        assert_eq!(directory_has_fresh_pid_lock(&dir.0).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lock_file_for_a_dead_pid_is_reported_stale_and_removed() {
+        let dir = TempDir::new("stale");
+
+        // A child we've waited on is guaranteed not to be alive, unlike a made-up pid which
+        // risks colliding with something actually running.
+        let mut child = std::process::Command::new("true")
+            .spawn()
+            .expect("Failed to spawn child");
+        let dead_pid = child.id();
+        child.wait().expect("Failed to wait for child");
+
+        let lock_path = pid_lock_path(&dir.0, dead_pid);
+        std::fs::write(
+            &lock_path,
+            format!("{}\n{}\n", dead_pid, local_hostname().unwrap()),
+        )
+        .unwrap();
+
+        assert_eq!(directory_has_fresh_pid_lock(&dir.0).unwrap(), Some(false));
+        assert!(!lock_path.exists());
+    }
+}
@@ -1,6 +1,8 @@
 mod artifacts;
 mod cleanup;
+mod digest;
 mod extract;
+mod lock;
 mod log;
 mod node;
 mod signal;
@@ -8,7 +10,9 @@ mod terminate;
 
 use eyre::{Result, WrapErr};
 use std::process::exit;
+use std::sync::mpsc::Receiver;
 
+use crate::cleanup::CleanupOutcome;
 use crate::extract::extract_archives_if_needed;
 
 const EXIT_GARDEN_SEA_ERROR: i32 = 11;
@@ -18,7 +22,8 @@ fn main() -> Result<()> {
         .expect("Failed to get temporary directory");
 
     let tmp_root = directories.data_dir();
-    let extracted_root = extract_archives_if_needed(tmp_root).wrap_err("Failed self-extract")?;
+    let (extracted_root, pid_lock, cleanup_rx) =
+        extract_archives_if_needed(tmp_root).wrap_err("Failed self-extract")?;
 
     let child =
         node::spawn_garden(&extracted_root, std::env::args()).wrap_err("Failed to spawn garden")?;
@@ -28,6 +33,54 @@ fn main() -> Result<()> {
     let exit_code =
         node::wait(child).wrap_err_with(|| format!("Failed waiting for garden (pid {})", pid))?;
 
+    // std::process::exit below doesn't run destructors, so drop the pid lock explicitly to
+    // remove it on this, the normal exit path.
+    drop(pid_lock);
+
+    // Cleanup runs detached in the background precisely so users don't wait for it, so by the
+    // time garden itself has exited we print whatever outcomes have arrived so far rather than
+    // blocking on the rest — a best-effort summary, not a final report.
+    print_cleanup_summary(&cleanup_rx);
+
     // we need to unwrap, as in case the child was terminated by a signal, we don't have an exit code
     exit(exit_code.unwrap_or(EXIT_GARDEN_SEA_ERROR))
 }
+
+/// Prints a one-line summary of what the background cleanup thread reclaimed (and failed to),
+/// covering whatever [`CleanupOutcome`]s have arrived on `rx` without blocking for more. Stays
+/// silent if nothing has been removed or failed, so a normal run without stale directories to
+/// clean up produces no extra output.
+fn print_cleanup_summary(rx: &Receiver<CleanupOutcome>) {
+    let mut removed = 0;
+    let mut failed = 0;
+
+    for outcome in rx.try_iter() {
+        match outcome {
+            CleanupOutcome::Removed(_) => removed += 1,
+            CleanupOutcome::Failed(_, _) => failed += 1,
+            CleanupOutcome::SkippedInUse(_)
+            | CleanupOutcome::SkippedCurrent(_)
+            | CleanupOutcome::SkippedProtected(_)
+            | CleanupOutcome::SkippedRetained(_) => {}
+        }
+    }
+
+    if removed == 0 && failed == 0 {
+        return;
+    }
+
+    eprintln!(
+        "garden-sea: reclaimed {} stale extract director{}{}",
+        removed,
+        if removed == 1 { "y" } else { "ies" },
+        if failed > 0 {
+            format!(
+                ", failed to reclaim {} director{}",
+                failed,
+                if failed == 1 { "y" } else { "ies" }
+            )
+        } else {
+            String::new()
+        }
+    );
+}
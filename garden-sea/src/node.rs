@@ -9,7 +9,7 @@ use std::{
 
 use eyre::{Result, WrapErr};
 
-use crate::{log::debug, signal};
+use crate::{log::debug, signal, terminate};
 
 #[cfg(all(target_os = "linux"))]
 use crate::artifacts::TARGET_ENV;
@@ -103,8 +103,58 @@ where
         debug!("Environment variable: {:?}={:?}", env.0, env.1.unwrap());
     }
 
-    Command::spawn(&mut command)
-        .wrap_err_with(|| format!("Failed to spawn {:?} with {:?}", command.get_program(), command.get_args()))
+    // Node spawns its own tree of grandchildren (kubectl, helm, docker, build tools, ...). Put
+    // it in its own process group / Job Object so that `terminate::forward_and_escalate` can
+    // signal the whole tree instead of just the single `node` pid, which otherwise leaves
+    // grandchildren orphaned when Garden is interrupted.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        // Safety: `setpgid` is async-signal-safe and is the only thing we do between fork and
+        // exec here, so it's safe to call from the pre_exec hook.
+        //
+        // Deliberately setpgid, not setsid: setsid would also detach the child from the
+        // controlling terminal, breaking grandchildren (ssh, docker login, git credential
+        // helpers) that open /dev/tty directly for prompts. All we need for killpg is a process
+        // group of its own.
+        unsafe {
+            command.pre_exec(|| {
+                nix::unistd::setpgid(nix::unistd::Pid::from_raw(0), nix::unistd::Pid::from_raw(0))
+                    .map_err(|errno| std::io::Error::from_raw_os_error(errno as i32))?;
+                Ok(())
+            });
+        }
+    }
+
+    // Spawn suspended so the process can't create any grandchildren before it's been assigned to
+    // the Job Object below — otherwise a grandchild spawned fast enough could start outside the
+    // job and escape the kill-on-close teardown entirely.
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use windows::Win32::System::Threading::CREATE_SUSPENDED;
+
+        command.creation_flags(CREATE_SUSPENDED.0);
+    }
+
+    let child = Command::spawn(&mut command).wrap_err_with(|| {
+        format!(
+            "Failed to spawn {:?} with {:?}",
+            command.get_program(),
+            command.get_args()
+        )
+    })?;
+
+    #[cfg(windows)]
+    {
+        terminate::assign_child_to_job(&child)
+            .wrap_err("Failed to assign node process to Job Object")?;
+        terminate::resume_main_thread(child.id())
+            .wrap_err("Failed to resume suspended node process")?;
+    }
+
+    Ok(child)
 }
 
 pub(crate) fn wait(mut child: Child) -> Result<Option<i32>> {
@@ -11,6 +11,12 @@ use windows::Win32::System::Console::{
     CTRL_SHUTDOWN_EVENT,
 };
 
+#[cfg(unix)]
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM},
+    iterator::Signals,
+};
+
 use crate::log::debug;
 use crate::terminate;
 
@@ -52,8 +58,8 @@ pub fn set_console_ctrl_handler(pid: u32) -> windows::core::Result<()> {
           recv(RECEIVE) -> msg => {
             if let Ok(signal) = msg {
               debug!("Received signal {:?}", signal);
-              if !terminate::interrupt(pid).is_ok() {
-                debug!("Failed to forward signal {:?} to process: {:?}", signal, pid);
+              if let Err(e) = terminate::forward_and_escalate(pid) {
+                debug!("Failed to forward signal {:?} to process: {:?}", signal, e);
               }
             } else {
               debug!("Receive error: ${:?}", msg);
@@ -65,17 +71,26 @@ pub fn set_console_ctrl_handler(pid: u32) -> windows::core::Result<()> {
     Ok(())
 }
 
+// On unix, Garden can be asked to shut down by more than just Ctrl-C: a process supervisor,
+// systemd, or a plain `kill` will send SIGTERM, SIGHUP or SIGQUIT, and those need forwarding
+// to the child just as much as SIGINT does. `signal_hook::iterator::Signals` lets us register
+// all of them and react to whichever one actually arrives, instead of hard-coding SIGINT.
 #[cfg(unix)]
-pub fn set_console_ctrl_handler(pid: u32) -> Result<(), nix::errno::Errno> {
-    ctrlc::set_handler(move || {
-        debug!("Received Ctrl+C / SIGINT!");
-        let result = terminate::interrupt(pid);
-        match result {
-            Ok(_) => debug!("Successfully forwarded ctrlc to process: {:?}", pid),
-            Err(e) => debug!("Failed to forward ctrlc to process: {:?}", e),
+pub fn set_console_ctrl_handler(pid: u32) -> Result<(), std::io::Error> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP, SIGQUIT])?;
+
+    std::thread::spawn(move || {
+        for sig in signals.forever() {
+            debug!("Received signal {}", sig);
+
+            let signal = nix::sys::signal::Signal::try_from(sig).unwrap_or(nix::sys::signal::Signal::SIGTERM);
+            let result = terminate::forward_and_escalate(pid, signal);
+            match result {
+                Ok(_) => debug!("Successfully forwarded signal {} to process: {:?}", sig, pid),
+                Err(e) => debug!("Failed to forward signal {} to process: {:?}", sig, e),
+            }
         }
-    })
-    .expect("Error setting Ctrl-C handler");
+    });
 
     Ok(())
 }
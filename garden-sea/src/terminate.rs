@@ -1,3 +1,6 @@
+use std::env;
+use std::time::Duration;
+
 #[cfg(unix)]
 use nix::unistd::Pid;
 
@@ -9,19 +12,229 @@ use windows::Win32::System::Console::{
     AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, CTRL_C_EVENT,
 };
 
-#[cfg(unix)]
-pub fn interrupt(pid: u32) -> Result<(), nix::errno::Errno> {
-    signal::kill(Pid::from_raw(pid.try_into().unwrap()), Signal::SIGINT)?;
+#[cfg(windows)]
+use lazy_static::lazy_static;
+#[cfg(windows)]
+use std::process::Child;
+#[cfg(windows)]
+use std::sync::Mutex;
+#[cfg(windows)]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(windows)]
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+};
+#[cfg(windows)]
+use windows::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+    SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+    JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+};
+#[cfg(windows)]
+use windows::Win32::System::Threading::{
+    OpenProcess, OpenThread, ResumeThread, WaitForSingleObject, PROCESS_SYNCHRONIZE,
+    THREAD_SUSPEND_RESUME, WAIT_OBJECT_0,
+};
+
+use crate::log::debug;
+
+const DEFAULT_KILL_TIMEOUT_SECS: u64 = 5;
+
+/// How long to wait for the child (tree) to exit after a signal is forwarded, before
+/// escalating to the next, harsher one. Configurable via `GARDEN_SEA_KILL_TIMEOUT` (seconds),
+/// since a wedged Node process shouldn't be able to hang the launcher indefinitely.
+fn kill_timeout() -> Duration {
+    env::var("GARDEN_SEA_KILL_TIMEOUT")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_KILL_TIMEOUT_SECS))
+}
+
+// On Windows there is no process-group equivalent of `setpgid`; instead the node child is put in
+// a Job Object configured to kill every process in it once the job is closed or terminated. This
+// handle is kept here (rather than in `node`) so the escalation logic below can reach it when a
+// forwarded signal needs to tear down the whole subprocess tree, not just the `node` process.
+#[cfg(windows)]
+lazy_static! {
+    static ref JOB_OBJECT: Mutex<Option<HANDLE>> = Mutex::new(None);
+}
+
+/// Puts `child` (the node process) into a dedicated Job Object so that its whole subprocess
+/// tree (kubectl, helm, docker, build tools, ...) can be torn down as a unit.
+#[cfg(windows)]
+pub fn assign_child_to_job(child: &Child) -> windows::core::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    unsafe {
+        let job = CreateJobObjectW(None, None)?;
+
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+
+        SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const _ as *const _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        )?;
+
+        let process = HANDLE(child.as_raw_handle() as _);
+        AssignProcessToJobObject(job, process)?;
+
+        *JOB_OBJECT.lock().expect("job object mutex poisoned") = Some(job);
+    }
+
+    Ok(())
+}
+
+/// Resumes the (only) thread of `pid`, a process spawned with `CREATE_SUSPENDED`.
+/// `node::spawn_garden` assigns the job object before resuming, so no grandchild can be created and escape job
+/// membership before `assign_child_to_job` runs.
+#[cfg(windows)]
+pub fn resume_main_thread(pid: u32) -> windows::core::Result<()> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0)?;
+
+        let mut entry = THREADENTRY32 {
+            dwSize: std::mem::size_of::<THREADENTRY32>() as u32,
+            ..Default::default()
+        };
+
+        let mut has_entry = Thread32First(snapshot, &mut entry).is_ok();
+        while has_entry {
+            if entry.th32OwnerProcessID == pid {
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, false, entry.th32ThreadID)?;
+                ResumeThread(thread);
+                let _ = CloseHandle(thread);
+                break;
+            }
+            has_entry = Thread32Next(snapshot, &mut entry).is_ok();
+        }
+
+        let _ = CloseHandle(snapshot);
+    }
+
     Ok(())
 }
 
+/// Terminates every process in the node child's Job Object, i.e. the whole subprocess tree,
+/// not just the `node` process itself.
 #[cfg(windows)]
-pub fn interrupt(pid: u32) -> windows::core::Result<()> {
+fn terminate_job_object() -> windows::core::Result<()> {
+    if let Some(job) = *JOB_OBJECT.lock().expect("job object mutex poisoned") {
+        unsafe { TerminateJobObject(job, 1)? };
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn forward(pid: u32, sig: Signal) -> Result<(), nix::errno::Errno> {
+    // `node::spawn_garden` places the node child in its own process group (with the child's pid
+    // as the group's pgid), so signalling the group reaches every grandchild it spawned
+    // (kubectl, helm, docker, build tools, ...) instead of leaving them orphaned.
+    signal::killpg(Pid::from_raw(pid.try_into().unwrap()), sig)
+}
+
+#[cfg(unix)]
+fn group_is_alive(pgid: u32) -> bool {
+    // `killpg` with signal 0 doesn't actually signal anything, only checks whether the process
+    // group still exists (it returns ESRCH once every member has exited). Checking the group
+    // rather than just the `node` leader's pid matters: if `node` itself reaps quickly after a
+    // forwarded signal but a grandchild (a wedged `kubectl`/`docker` build, say) is still alive
+    // in the same group, the group lives on and escalation correctly continues instead of
+    // declaring victory the moment the leader is gone.
+    match signal::killpg(Pid::from_raw(pgid.try_into().unwrap()), None) {
+        Ok(()) => true,
+        Err(nix::errno::Errno::ESRCH) => false,
+        // e.g. EPERM: the group still exists, we just can't signal it. Treat as alive.
+        Err(_) => true,
+    }
+}
+
+#[cfg(unix)]
+fn wait_for_exit(pgid: u32, timeout: Duration) -> bool {
+    let poll_interval = Duration::from_millis(100);
+    let mut waited = Duration::ZERO;
+
+    while group_is_alive(pgid) {
+        if waited >= timeout {
+            return false;
+        }
+        std::thread::sleep(poll_interval);
+        waited += poll_interval;
+    }
+
+    true
+}
+
+/// Forwards `sig` to the node process (and, via its process group, every subprocess it spawned),
+/// then waits up to [`kill_timeout`] for it to exit. If it's still alive, escalates to SIGTERM
+/// and finally SIGKILL so a wedged Node process can't hang the launcher indefinitely.
+#[cfg(unix)]
+pub fn forward_and_escalate(pid: u32, sig: Signal) -> Result<(), nix::errno::Errno> {
+    let grace = kill_timeout();
+
+    debug!("Forwarding signal {:?} to process group {}", sig, pid);
+    forward(pid, sig)?;
+    if wait_for_exit(pid, grace) {
+        return Ok(());
+    }
+
+    debug!(
+        "Process group {} still alive {:?} after {:?}, escalating to SIGTERM",
+        pid, grace, sig
+    );
+    forward(pid, Signal::SIGTERM)?;
+    if wait_for_exit(pid, grace) {
+        return Ok(());
+    }
+
+    debug!(
+        "Process group {} still alive after SIGTERM, escalating to SIGKILL",
+        pid
+    );
+    forward(pid, Signal::SIGKILL)
+}
+
+#[cfg(windows)]
+fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    unsafe {
+        let Ok(process) = OpenProcess(PROCESS_SYNCHRONIZE, false, pid) else {
+            // We can't open the process anymore, so it's gone.
+            return true;
+        };
+
+        let exited = WaitForSingleObject(process, timeout.as_millis() as u32) == WAIT_OBJECT_0;
+        let _ = CloseHandle(process);
+
+        exited
+    }
+}
+
+/// Sends a CTRL event to the node process' console, then waits up to [`kill_timeout`] for it to
+/// exit. If it's still alive, escalates by terminating the whole Job Object (there is no
+/// Windows equivalent of SIGTERM/SIGKILL to step through first).
+#[cfg(windows)]
+pub fn forward_and_escalate(pid: u32) -> windows::core::Result<()> {
+    let grace = kill_timeout();
+
+    debug!("Forwarding CTRL event to process {}", pid);
     unsafe {
         FreeConsole()?;
         AttachConsole(pid)?;
         SetConsoleCtrlHandler(None, true)?;
         GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0)?;
     }
-    Ok(())
+
+    if wait_for_exit(pid, grace) {
+        return Ok(());
+    }
+
+    debug!(
+        "Process {} still alive after {:?}, escalating to Job Object termination",
+        pid, grace
+    );
+    terminate_job_object()
 }